@@ -4,6 +4,7 @@ use std::ops;
 
 use crate::array::DataChunk;
 use crate::types::{DataTypeKind, Datum, DatumRef, ScalarImpl, ToOwnedDatum};
+use crate::util::sort_util::OrderType;
 
 impl DataChunk {
     pub fn rows(&self) -> DataChunkRefIter<'_> {
@@ -137,30 +138,109 @@ impl Row {
         }
         Ok(serializer.into_inner())
     }
+
+    /// Serialize the row into memcomparable bytes, bit-flipping each [`OrderType::Descending`]
+    /// column (including its null tag) via [`memcomparable::Serializer::set_reverse`].
+    pub fn serialize_with_order(
+        &self,
+        order_types: &[OrderType],
+    ) -> Result<Vec<u8>, memcomparable::Error> {
+        assert_eq!(self.0.len(), order_types.len());
+        let mut serializer = memcomparable::Serializer::default();
+        for (v, order_type) in self.0.iter().zip(order_types.iter()) {
+            serializer.set_reverse(*order_type == OrderType::Descending);
+            if let Some(v) = v {
+                1u8.serialize(&mut serializer)?;
+                v.serialize(&mut serializer)?;
+            } else {
+                0u8.serialize(&mut serializer)?;
+            }
+        }
+        Ok(serializer.into_inner())
+    }
+}
+
+/// A column of the schema a [`RowDeserializer`] decodes into, together with the value to use
+/// when an encoded row predates this column (see [`RowDeserializer::with_schema_evolution`]).
+#[derive(Clone, Debug)]
+pub struct ColumnDecodeSpec {
+    pub ty: DataTypeKind,
+    /// Substituted when the column's bytes are absent from the input, e.g. because the column
+    /// was added by `ALTER TABLE ADD COLUMN` after the row was written. `None` is SQL NULL.
+    pub default: Datum,
+}
+
+impl From<DataTypeKind> for ColumnDecodeSpec {
+    fn from(ty: DataTypeKind) -> Self {
+        Self { ty, default: None }
+    }
+}
+
+/// Where one column position of an *encoded* row maps to in the current schema.
+#[derive(Clone, Debug)]
+pub enum ColumnMapping {
+    /// Decode normally and store the result at this index of the target schema.
+    Target(usize),
+    /// The column was removed from the schema by `ALTER TABLE DROP COLUMN` after the row was
+    /// written; its bytes must still be consumed to keep later columns aligned, but the decoded
+    /// value is discarded.
+    Dropped(DataTypeKind),
 }
 
 /// Deserializer of the `Row`.
 pub struct RowDeserializer {
-    schema: Vec<DataTypeKind>,
+    /// The table's current column list, newest first-class schema.
+    schema: Vec<ColumnDecodeSpec>,
+    /// One entry per column position present in the encoded bytes, in encoded order. Shorter
+    /// than `schema` when columns were added after the row was written; those trailing `schema`
+    /// columns keep their `default` instead of being read from `data`.
+    projection: Vec<ColumnMapping>,
 }
 
 impl RowDeserializer {
-    /// Creates a new `RowDeserializer` with row schema.
+    /// Creates a new `RowDeserializer` with row schema, assuming `data` exactly matches it.
     pub fn new(schema: Vec<DataTypeKind>) -> Self {
-        RowDeserializer { schema }
+        let projection = (0..schema.len()).map(ColumnMapping::Target).collect();
+        Self {
+            schema: schema.into_iter().map(ColumnDecodeSpec::from).collect(),
+            projection,
+        }
+    }
+
+    /// Creates a `RowDeserializer` tolerant of rows encoded under an older version of `schema`,
+    /// as produced across an `ALTER TABLE ADD/DROP COLUMN` sequence.
+    ///
+    /// `schema` is the table's current columns; `projection` describes, for each column that was
+    /// actually present when the row was written, whether it still exists in `schema` (and at
+    /// which index) or has since been dropped. Columns in `schema` with no corresponding
+    /// `ColumnMapping::Target` entry are filled from their `default`.
+    pub fn with_schema_evolution(
+        schema: Vec<ColumnDecodeSpec>,
+        projection: Vec<ColumnMapping>,
+    ) -> Self {
+        Self { schema, projection }
     }
 
     /// Deserialize the row from a memcomparable bytes.
     pub fn deserialize(&self, data: &[u8]) -> Result<Row, memcomparable::Error> {
-        let mut values = vec![];
-        values.reserve(self.schema.len());
+        let mut values: Vec<Datum> = self.schema.iter().map(|c| c.default.clone()).collect();
         let mut deserializer = memcomparable::Deserializer::from_slice(data);
-        for &ty in self.schema.iter() {
+        for mapping in self.projection.iter() {
+            let ty = match mapping {
+                ColumnMapping::Target(idx) => self.schema[*idx].ty,
+                ColumnMapping::Dropped(ty) => *ty,
+            };
             match u8::deserialize(&mut deserializer)? {
-                0 => values.push(None),
+                0 => {
+                    if let ColumnMapping::Target(idx) = mapping {
+                        values[*idx] = None;
+                    }
+                }
                 1 => {
                     let scalar = ScalarImpl::deserialize(ty, &mut deserializer)?;
-                    values.push(Some(scalar));
+                    if let ColumnMapping::Target(idx) = mapping {
+                        values[*idx] = Some(scalar);
+                    }
                 }
                 t => return Err(memcomparable::Error::InvalidTagEncoding(t as _)),
             }
@@ -173,12 +253,49 @@ impl RowDeserializer {
         let mut values = vec![];
         values.reserve(self.schema.len());
         let mut deserializer = memcomparable::Deserializer::from_slice(data);
-        for &ty in self.schema.iter() {
-            let scalar = ScalarImpl::deserialize(ty, &mut deserializer)?;
+        for c in self.schema.iter() {
+            let scalar = ScalarImpl::deserialize(c.ty, &mut deserializer)?;
             values.push(Some(scalar));
         }
         Ok(Row(values))
     }
+
+    /// Deserialize the row from memcomparable bytes produced by [`Row::serialize_with_order`].
+    ///
+    /// `order_types` must line up with the *encoded* columns (one per `self.projection` entry,
+    /// same per-column direction used to encode them), not necessarily the current schema --
+    /// same projection-aware handling as [`Self::deserialize`].
+    pub fn deserialize_with_order(
+        &self,
+        data: &[u8],
+        order_types: &[OrderType],
+    ) -> Result<Row, memcomparable::Error> {
+        assert_eq!(self.projection.len(), order_types.len());
+        let mut values: Vec<Datum> = self.schema.iter().map(|c| c.default.clone()).collect();
+        let mut deserializer = memcomparable::Deserializer::from_slice(data);
+        for (mapping, order_type) in self.projection.iter().zip(order_types.iter()) {
+            let ty = match mapping {
+                ColumnMapping::Target(idx) => self.schema[*idx].ty,
+                ColumnMapping::Dropped(ty) => *ty,
+            };
+            deserializer.set_reverse(*order_type == OrderType::Descending);
+            match u8::deserialize(&mut deserializer)? {
+                0 => {
+                    if let ColumnMapping::Target(idx) = mapping {
+                        values[*idx] = None;
+                    }
+                }
+                1 => {
+                    let scalar = ScalarImpl::deserialize(ty, &mut deserializer)?;
+                    if let ColumnMapping::Target(idx) = mapping {
+                        values[*idx] = Some(scalar);
+                    }
+                }
+                t => return Err(memcomparable::Error::InvalidTagEncoding(t as _)),
+            }
+        }
+        Ok(Row(values))
+    }
 }
 
 #[cfg(test)]
@@ -247,4 +364,122 @@ mod tests {
         let row1 = de.deserialize(&bytes).unwrap();
         assert_eq!(row, row1);
     }
+
+    #[test]
+    fn row_memcomparable_encode_decode_with_order() {
+        let order_types = vec![OrderType::Ascending, OrderType::Ascending];
+        let de = RowDeserializer::new(vec![Ty::Int32, Ty::Int32]);
+
+        let row1 = Row(vec![Some(ScalarImpl::Int32(1)), Some(ScalarImpl::Int32(2))]);
+        let row2 = Row(vec![Some(ScalarImpl::Int32(1)), Some(ScalarImpl::Int32(3))]);
+
+        let bytes1 = row1.serialize_with_order(&order_types).unwrap();
+        let bytes2 = row2.serialize_with_order(&order_types).unwrap();
+        assert!(bytes1 < bytes2);
+
+        assert_eq!(
+            de.deserialize_with_order(&bytes1, &order_types).unwrap(),
+            row1
+        );
+        assert_eq!(
+            de.deserialize_with_order(&bytes2, &order_types).unwrap(),
+            row2
+        );
+
+        // Flip the second column to `DESC`: the relative order of the two rows reverses, and a
+        // null in that column now sorts after any present value instead of before it.
+        let order_types = vec![OrderType::Ascending, OrderType::Descending];
+        let bytes1 = row1.serialize_with_order(&order_types).unwrap();
+        let bytes2 = row2.serialize_with_order(&order_types).unwrap();
+        assert!(bytes1 > bytes2);
+
+        let row_null = Row(vec![Some(ScalarImpl::Int32(1)), None]);
+        let bytes_null = row_null.serialize_with_order(&order_types).unwrap();
+        assert!(bytes_null > bytes1);
+        assert_eq!(
+            de.deserialize_with_order(&bytes_null, &order_types)
+                .unwrap(),
+            row_null
+        );
+    }
+
+    #[test]
+    fn row_deserializer_tolerates_add_then_drop_column() {
+        // v1 schema: (id int, name varchar). A row is written under it.
+        let v1 = RowDeserializer::new(vec![Ty::Int32, Ty::Varchar]);
+        let row_v1 = Row(vec![
+            Some(ScalarImpl::Int32(1)),
+            Some(ScalarImpl::Utf8("alice".into())),
+        ]);
+        let bytes = row_v1.serialize().unwrap();
+
+        // v2 schema: `ALTER TABLE ADD COLUMN age int DEFAULT 0` appends a column; the old bytes
+        // don't contain it, so it should come back as the column default.
+        let v2 = RowDeserializer::with_schema_evolution(
+            vec![
+                ColumnDecodeSpec::from(Ty::Int32),
+                ColumnDecodeSpec::from(Ty::Varchar),
+                ColumnDecodeSpec {
+                    ty: Ty::Int32,
+                    default: Some(ScalarImpl::Int32(0)),
+                },
+            ],
+            vec![ColumnMapping::Target(0), ColumnMapping::Target(1)],
+        );
+        let row_v2 = v2.deserialize(&bytes).unwrap();
+        assert_eq!(
+            row_v2,
+            Row(vec![
+                Some(ScalarImpl::Int32(1)),
+                Some(ScalarImpl::Utf8("alice".into())),
+                Some(ScalarImpl::Int32(0)),
+            ])
+        );
+
+        // v3 schema: `ALTER TABLE DROP COLUMN name` removes the middle column again. Rows
+        // written under v2 still have its bytes on disk, so decoding them must skip over that
+        // column's bytes while keeping `id` and `age` aligned.
+        let v3 = RowDeserializer::with_schema_evolution(
+            vec![
+                ColumnDecodeSpec::from(Ty::Int32),
+                ColumnDecodeSpec {
+                    ty: Ty::Int32,
+                    default: Some(ScalarImpl::Int32(0)),
+                },
+            ],
+            vec![
+                ColumnMapping::Target(0),
+                ColumnMapping::Dropped(Ty::Varchar),
+                ColumnMapping::Target(1),
+            ],
+        );
+        let row_v2_bytes = row_v2.serialize().unwrap();
+        let row_v3 = v3.deserialize(&row_v2_bytes).unwrap();
+        assert_eq!(
+            row_v3,
+            Row(vec![Some(ScalarImpl::Int32(1)), Some(ScalarImpl::Int32(0))])
+        );
+    }
+
+    #[test]
+    fn row_deserializer_with_order_tolerates_dropped_column() {
+        // v2 schema: (id int, name varchar). Rows are written order-aware under it.
+        let v2 = RowDeserializer::new(vec![Ty::Int32, Ty::Varchar]);
+        let order_types = vec![OrderType::Ascending, OrderType::Descending];
+        let row_v2 = Row(vec![
+            Some(ScalarImpl::Int32(1)),
+            Some(ScalarImpl::Utf8("alice".into())),
+        ]);
+        let bytes = row_v2.serialize_with_order(&order_types).unwrap();
+
+        // v3 schema: `ALTER TABLE DROP COLUMN name`. `order_types` still has one entry per
+        // *encoded* column, so decoding the old bytes must skip over `name`'s bytes (using its
+        // own direction) while keeping `id` aligned.
+        let v3 = RowDeserializer::with_schema_evolution(
+            vec![ColumnDecodeSpec::from(Ty::Int32)],
+            vec![ColumnMapping::Target(0), ColumnMapping::Dropped(Ty::Varchar)],
+        );
+        let row_v3 = v3.deserialize_with_order(&bytes, &order_types).unwrap();
+        assert_eq!(row_v3, Row(vec![Some(ScalarImpl::Int32(1))]));
+    }
 }
@@ -1,27 +1,132 @@
+use std::net::TcpStream;
+use std::thread;
+use std::time::{Duration, Instant};
+
 use anyhow::Result;
+use thiserror::Error;
 
 use super::{ExecuteContext, Task};
 
+/// How long to wait for any single port to stop listening before giving up on it.
+const DEFAULT_PORT_TIMEOUT: Duration = Duration::from_secs(10);
+/// Upper bound on the whole "ensure stopped" stage, regardless of how many ports it watches.
+const DEFAULT_TOTAL_TIMEOUT: Duration = Duration::from_secs(30);
+/// How often to retry a `connect` while polling a port.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
 pub struct EnsureStopService {
     ports: Vec<u16>,
+    port_timeout: Duration,
+    total_timeout: Duration,
 }
 
 impl EnsureStopService {
     pub fn new(ports: Vec<u16>) -> Result<Self> {
-        Ok(Self { ports })
+        Self::with_timeout(ports, DEFAULT_PORT_TIMEOUT, DEFAULT_TOTAL_TIMEOUT)
+    }
+
+    /// Like [`Self::new`], but with explicit per-port and whole-stage deadlines.
+    pub fn with_timeout(
+        ports: Vec<u16>,
+        port_timeout: Duration,
+        total_timeout: Duration,
+    ) -> Result<Self> {
+        Ok(Self {
+            ports,
+            port_timeout,
+            total_timeout,
+        })
+    }
+}
+
+/// A port that was still accepting connections when its timeout elapsed.
+///
+/// Carries the port (and, if discoverable, the pid holding it) so callers can `downcast_ref`
+/// this out of the returned `anyhow::Error` and act on it, e.g. force-kill `pid`.
+#[derive(Debug, Clone, Copy, Error)]
+#[error("port {port} is still in use{}", self.pid.map(|p| format!(" (held by pid {p})")).unwrap_or_default())]
+pub struct PortStillInUse {
+    pub port: u16,
+    pub pid: Option<u32>,
+}
+
+/// One or more ports failed to close before their timeout.
+#[derive(Debug, Error)]
+#[error("timed out waiting for previous services to stop: {}", .0.iter().map(ToString::to_string).collect::<Vec<_>>().join(", "))]
+pub struct EnsureStopTimeout(pub Vec<PortStillInUse>);
+
+/// Polls `address` until it stops accepting connections or `timeout` elapses.
+fn wait_tcp_close(address: &str, timeout: Duration) -> Result<(), ()> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        if TcpStream::connect(address).is_err() {
+            return Ok(());
+        }
+        if Instant::now() >= deadline {
+            return Err(());
+        }
+        thread::sleep(POLL_INTERVAL);
     }
 }
 
+/// Best-effort lookup of the PID listening on `port`. Returns `None` if it isn't discoverable
+/// this way (e.g. `lsof` is missing).
+#[cfg(unix)]
+fn find_listening_pid(port: u16) -> Option<u32> {
+    let output = std::process::Command::new("lsof")
+        .args(["-t", "-i", &format!("tcp:{}", port)])
+        .output()
+        .ok()?;
+    String::from_utf8(output.stdout)
+        .ok()?
+        .lines()
+        .next()?
+        .trim()
+        .parse()
+        .ok()
+}
+
+#[cfg(not(unix))]
+fn find_listening_pid(_port: u16) -> Option<u32> {
+    None
+}
+
 impl Task for EnsureStopService {
     fn execute(&mut self, ctx: &mut ExecuteContext<impl std::io::Write>) -> anyhow::Result<()> {
         ctx.service(self);
 
-        for port in &self.ports {
-            let address = format!("127.0.0.1:{}", port);
+        ctx.pb.set_message(format!(
+            "waiting for {} port(s) to close",
+            self.ports.len()
+        ));
+
+        // Poll every port concurrently instead of strictly sequentially, so the ports as a whole
+        // take as long as the slowest one rather than the sum of all of them. Each port's budget
+        // is clamped to whatever remains of `total_timeout` at the moment it starts.
+        let start = Instant::now();
+        let handles: Vec<_> = self
+            .ports
+            .iter()
+            .map(|&port| {
+                let address = format!("127.0.0.1:{}", port);
+                let remaining = self.total_timeout.saturating_sub(start.elapsed());
+                let timeout = self.port_timeout.min(remaining);
+                thread::spawn(move || {
+                    wait_tcp_close(&address, timeout).map_err(|_| PortStillInUse {
+                        port,
+                        pid: find_listening_pid(port),
+                    })
+                })
+            })
+            .collect();
+
+        let errors: Vec<PortStillInUse> = handles
+            .into_iter()
+            .filter_map(|handle| handle.join().expect("wait_tcp_close thread panicked").err())
+            .collect();
 
-            ctx.pb
-                .set_message(format!("waiting for port close - {}", address));
-            ctx.wait_tcp_close(&address)?;
+        if !errors.is_empty() {
+            return Err(EnsureStopTimeout(errors).into());
         }
 
         ctx.pb
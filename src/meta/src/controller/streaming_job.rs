@@ -0,0 +1,167 @@
+// Copyright 2024 RisingWave Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Persistence for [`BackgroundDdlProgress`], backing [`CatalogController::upsert_background_ddl_progress`]
+//! and friends. Lives next to the rest of `CatalogController`'s streaming-job-catalog methods
+//! (`create_job_catalog`, `prepare_streaming_job`, `finish_streaming_job`, ...).
+
+use sea_orm::entity::prelude::*;
+use sea_orm::sea_query::OnConflict;
+use sea_orm::ActiveValue::Set;
+
+use crate::controller::CatalogController;
+use crate::rpc::ddl_controller_v2::{BackgroundDdlPhase, BackgroundDdlProgress};
+use crate::MetaResult;
+
+mod background_ddl_progress {
+    use sea_orm::entity::prelude::*;
+
+    #[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
+    #[sea_orm(table_name = "background_ddl_progress")]
+    pub struct Model {
+        #[sea_orm(primary_key, auto_increment = false)]
+        pub job_id: i32,
+        /// [`super::BackgroundDdlPhase`] as an integer (see `From`/`TryFrom` in the parent
+        /// module); `i32` rather than a SeaORM enum to keep this entity self-contained.
+        pub phase: i32,
+        pub ctx: Vec<u8>,
+        pub table_fragments: Option<Vec<u8>>,
+    }
+
+    #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+    pub enum Relation {}
+
+    impl ActiveModelBehavior for ActiveModel {}
+}
+
+use background_ddl_progress::{
+    ActiveModel as BackgroundDdlProgressActiveModel, Column as BackgroundDdlProgressColumn,
+    Entity as BackgroundDdlProgressEntity, Model as BackgroundDdlProgressModel,
+};
+
+impl From<BackgroundDdlPhase> for i32 {
+    fn from(phase: BackgroundDdlPhase) -> Self {
+        match phase {
+            BackgroundDdlPhase::BuildingFragments => 0,
+            BackgroundDdlPhase::CreatingActors => 1,
+            BackgroundDdlPhase::Finishing => 2,
+        }
+    }
+}
+
+impl TryFrom<i32> for BackgroundDdlPhase {
+    type Error = i32;
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::BuildingFragments),
+            1 => Ok(Self::CreatingActors),
+            2 => Ok(Self::Finishing),
+            other => Err(other),
+        }
+    }
+}
+
+impl From<BackgroundDdlProgressModel> for BackgroundDdlProgress {
+    fn from(model: BackgroundDdlProgressModel) -> Self {
+        let phase = BackgroundDdlPhase::try_from(model.phase)
+            .unwrap_or_else(|p| panic!("invalid background_ddl_progress.phase: {p}"));
+        BackgroundDdlProgress {
+            job_id: model.job_id as u32,
+            phase,
+            ctx: model.ctx,
+            table_fragments: model.table_fragments,
+        }
+    }
+}
+
+impl CatalogController {
+    /// Upserts the checkpoint for a background streaming job, keyed by `job_id`.
+    pub async fn upsert_background_ddl_progress(
+        &self,
+        progress: BackgroundDdlProgress,
+    ) -> MetaResult<()> {
+        let inner = self.inner.read().await;
+        let active_model = BackgroundDdlProgressActiveModel {
+            job_id: Set(progress.job_id as i32),
+            phase: Set(progress.phase.into()),
+            ctx: Set(progress.ctx),
+            table_fragments: Set(progress.table_fragments),
+        };
+        BackgroundDdlProgressEntity::insert(active_model)
+            .on_conflict(
+                OnConflict::column(BackgroundDdlProgressColumn::JobId)
+                    .update_columns([
+                        BackgroundDdlProgressColumn::Phase,
+                        BackgroundDdlProgressColumn::Ctx,
+                        BackgroundDdlProgressColumn::TableFragments,
+                    ])
+                    .to_owned(),
+            )
+            .exec(&inner.db)
+            .await?;
+        Ok(())
+    }
+
+    /// Advances a persisted checkpoint's phase in place, leaving `ctx`/`table_fragments` as-is.
+    pub async fn set_background_ddl_phase(
+        &self,
+        job_id: u32,
+        phase: BackgroundDdlPhase,
+    ) -> MetaResult<()> {
+        let inner = self.inner.read().await;
+        BackgroundDdlProgressEntity::update_many()
+            .col_expr(
+                BackgroundDdlProgressColumn::Phase,
+                Expr::value(i32::from(phase)),
+            )
+            .filter(BackgroundDdlProgressColumn::JobId.eq(job_id as i32))
+            .exec(&inner.db)
+            .await?;
+        Ok(())
+    }
+
+    /// Deletes the checkpoint for `job_id`, if any. A no-op if it was already removed.
+    pub async fn remove_background_ddl_progress(&self, job_id: u32) -> MetaResult<()> {
+        let inner = self.inner.read().await;
+        BackgroundDdlProgressEntity::delete_by_id(job_id as i32)
+            .exec(&inner.db)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn get_background_ddl_progress(
+        &self,
+        job_id: u32,
+    ) -> MetaResult<Option<BackgroundDdlProgress>> {
+        let inner = self.inner.read().await;
+        Ok(BackgroundDdlProgressEntity::find_by_id(job_id as i32)
+            .one(&inner.db)
+            .await?
+            .map(Into::into))
+    }
+
+    /// Lists every persisted background DDL checkpoint, regardless of phase. Used by
+    /// [`crate::rpc::ddl_controller_v2::DdlController::recover_background_ddl_jobs`] to find
+    /// jobs to resume after a restart.
+    pub async fn list_background_ddl_progress(&self) -> MetaResult<Vec<BackgroundDdlProgress>> {
+        let inner = self.inner.read().await;
+        Ok(BackgroundDdlProgressEntity::find()
+            .all(&inner.db)
+            .await?
+            .into_iter()
+            .map(Into::into)
+            .collect())
+    }
+}
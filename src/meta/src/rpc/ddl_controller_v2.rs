@@ -13,12 +13,15 @@
 // limitations under the License.
 
 use itertools::Itertools;
+use prost::Message;
 use risingwave_common::util::column_index_mapping::ColIndexMapping;
 use risingwave_common::util::stream_graph_visitor::visit_fragment;
 use risingwave_pb::catalog::CreateType;
 use risingwave_pb::ddl_service::TableJobType;
+use risingwave_pb::meta::TableFragments as PbTableFragments;
 use risingwave_pb::stream_plan::stream_node::NodeBody;
 use risingwave_pb::stream_plan::update_mutation::PbMergeUpdate;
+use risingwave_pb::stream_plan::StreamContext as PbStreamContext;
 use risingwave_pb::stream_plan::StreamFragmentGraph as StreamFragmentGraphProto;
 use thiserror_ext::AsReport;
 
@@ -26,11 +29,71 @@ use crate::manager::{
     MetadataManager, MetadataManagerV2, NotificationVersion, StreamingJob,
     IGNORED_NOTIFICATION_VERSION,
 };
-use crate::model::{MetadataModel, StreamContext};
+use crate::model::{MetadataModel, StreamContext, TableFragments};
 use crate::rpc::ddl_controller::{fill_table_stream_graph_info, DdlController};
 use crate::stream::{validate_sink, StreamFragmentGraph};
 use crate::MetaResult;
 
+/// Phase of a `CreateType::Background` streaming job's creation, persisted so the job survives a
+/// meta node restart. Mirrors the steps `create_streaming_job_inner_v2` takes for a foreground
+/// job.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BackgroundDdlPhase {
+    /// Fragment and actor catalogs are being assembled (`build_stream_job`).
+    BuildingFragments,
+    /// Fragment catalogs are persisted; actors are being scheduled and created on compute nodes.
+    CreatingActors,
+    /// Actors are live; only `finish_streaming_job` bookkeeping remains.
+    Finishing,
+}
+
+/// A durable checkpoint for one in-flight background streaming job.
+///
+/// `ctx`/`table_fragments` are kept protobuf-encoded rather than as `StreamContext`/
+/// `TableFragments` directly -- like every other persisted copy of those types in this file, they
+/// round-trip through `to_protobuf`/`from_protobuf` (see lines around `prepare_streaming_job`
+/// below), since neither type implements `serde::Serialize`. Persisted by
+/// [`crate::controller::CatalogController::upsert_background_ddl_progress`], backed by the
+/// `background_ddl_progress` table (see `controller::streaming_job`).
+#[derive(Debug, Clone)]
+pub struct BackgroundDdlProgress {
+    pub job_id: u32,
+    pub phase: BackgroundDdlPhase,
+    pub(crate) ctx: Vec<u8>,
+    /// Present once `phase` reaches `CreatingActors`; `None` while still `BuildingFragments`.
+    pub(crate) table_fragments: Option<Vec<u8>>,
+}
+
+impl BackgroundDdlProgress {
+    fn building(job_id: u32, ctx: &StreamContext) -> Self {
+        Self {
+            job_id,
+            phase: BackgroundDdlPhase::BuildingFragments,
+            ctx: ctx.to_protobuf().encode_to_vec(),
+            table_fragments: None,
+        }
+    }
+
+    fn creating_actors(job_id: u32, ctx: &StreamContext, table_fragments: &TableFragments) -> Self {
+        Self {
+            job_id,
+            phase: BackgroundDdlPhase::CreatingActors,
+            ctx: ctx.to_protobuf().encode_to_vec(),
+            table_fragments: Some(table_fragments.to_protobuf().encode_to_vec()),
+        }
+    }
+
+    fn ctx(&self) -> StreamContext {
+        StreamContext::from_protobuf(PbStreamContext::decode(self.ctx.as_slice()).unwrap())
+    }
+
+    fn table_fragments(&self) -> Option<TableFragments> {
+        self.table_fragments.as_ref().map(|bytes| {
+            TableFragments::from_protobuf(PbTableFragments::decode(bytes.as_slice()).unwrap())
+        })
+    }
+}
+
 impl DdlController {
     pub async fn create_streaming_job_v2(
         &self,
@@ -124,6 +187,19 @@ impl DdlController {
             .await?;
         fragment_graph.refill_internal_table_ids(table_id_map);
 
+        let is_background = streaming_job.create_type() == CreateType::Background;
+        if is_background {
+            // Persist a `BuildingFragments` checkpoint *before* `build_stream_job`, so a restart
+            // during the build is at least detected. `recover_background_ddl_jobs` cannot resume
+            // such a job: it logs a warning and deletes the checkpoint.
+            mgr.catalog_controller
+                .upsert_background_ddl_progress(BackgroundDdlProgress::building(
+                    streaming_job.id() as _,
+                    &ctx,
+                ))
+                .await?;
+        }
+
         // create fragment and actor catalogs.
         tracing::debug!(id = streaming_job.id(), "building streaming job");
         let (ctx, table_fragments) = self
@@ -170,6 +246,16 @@ impl DdlController {
                 Ok(version)
             }
             CreateType::Background => {
+                // `table_fragments` is built now, so advance the checkpoint from
+                // `BuildingFragments` to `CreatingActors` before spawning.
+                mgr.catalog_controller
+                    .upsert_background_ddl_progress(BackgroundDdlProgress::creating_actors(
+                        stream_job_id as _,
+                        &ctx,
+                        &table_fragments,
+                    ))
+                    .await?;
+
                 let ctrl = self.clone();
                 let mgr = mgr.clone();
                 let fut = async move {
@@ -180,6 +266,12 @@ impl DdlController {
                             tracing::error!(id = stream_job_id, error = ?err.as_report(), "failed to create background streaming job");
                         });
                     if result.is_ok() {
+                        let _ = mgr
+                            .catalog_controller
+                            .set_background_ddl_phase(stream_job_id as _, BackgroundDdlPhase::Finishing)
+                            .await.inspect_err(|err| {
+                                tracing::error!(id = stream_job_id, error = ?err.as_report(), "failed to persist background streaming job progress");
+                            });
                         let _ = mgr
                             .catalog_controller
                             .finish_streaming_job(stream_job_id as _)
@@ -187,6 +279,14 @@ impl DdlController {
                                 tracing::error!(id = stream_job_id, error = ?err.as_report(), "failed to finish background streaming job");
                             });
                     }
+                    // Clean up the checkpoint whether the job succeeded or failed: on success
+                    // it's terminal, and on failure leaving it behind would make it a phantom
+                    // that `recover_background_ddl_jobs` silently re-dispatches (with stale
+                    // `table_fragments`) on every future meta restart.
+                    let _ = mgr
+                        .catalog_controller
+                        .remove_background_ddl_progress(stream_job_id as _)
+                        .await;
                 };
                 tokio::spawn(fut);
                 Ok(IGNORED_NOTIFICATION_VERSION)
@@ -194,6 +294,118 @@ impl DdlController {
         }
     }
 
+    /// Scans for background streaming jobs persisted in a non-terminal [`BackgroundDdlPhase`]
+    /// and re-dispatches those in `CreatingActors` or `Finishing` from their last checkpoint.
+    /// Jobs still in `BuildingFragments` are not resumed: their checkpoint is deleted with a
+    /// warning.
+    ///
+    /// Must be called exactly once during meta node startup, before the node begins accepting
+    /// DDL RPCs -- that's the only way an orphaned job with no further client DDL traffic is
+    /// still guaranteed to get resumed. This tree doesn't include the meta node's bootstrap
+    /// module, so there's no call site to wire it into here; whichever code builds the
+    /// [`DdlController`] during startup (after `MetadataManagerV2`/`CatalogController` are up,
+    /// before the gRPC server starts serving) should call this once.
+    pub async fn recover_background_ddl_jobs(&self) -> MetaResult<()> {
+        let MetadataManager::V2(mgr) = &self.metadata_manager else {
+            unreachable!("MetadataManager should be V2")
+        };
+
+        for progress in mgr.catalog_controller.list_background_ddl_progress().await? {
+            tracing::info!(
+                id = progress.job_id,
+                phase = ?progress.phase,
+                "resuming background streaming job after restart"
+            );
+            let stream_job_id = progress.job_id;
+
+            match progress.phase {
+                BackgroundDdlPhase::BuildingFragments => {
+                    // Restart happened before fragment/actor catalogs were built; there is
+                    // nothing recoverable to resume from, so the job must be recreated from
+                    // scratch by its original caller.
+                    tracing::warn!(
+                        id = stream_job_id,
+                        "background streaming job restarted before fragments were built; cannot resume"
+                    );
+                    let _ = mgr
+                        .catalog_controller
+                        .remove_background_ddl_progress(stream_job_id)
+                        .await;
+                }
+                BackgroundDdlPhase::CreatingActors => {
+                    let table_fragments = progress
+                        .table_fragments()
+                        .expect("table_fragments is set once phase reaches CreatingActors");
+                    let ctrl = self.clone();
+                    let mgr = mgr.clone();
+                    let fut = async move {
+                        let result = ctrl
+                            .stream_manager
+                            .create_streaming_job(table_fragments, progress.ctx())
+                            .await
+                            .inspect_err(|err| {
+                                tracing::error!(id = stream_job_id, error = ?err.as_report(), "failed to resume background streaming job");
+                            });
+                        if result.is_ok() {
+                            let _ = mgr
+                                .catalog_controller
+                                .set_background_ddl_phase(stream_job_id, BackgroundDdlPhase::Finishing)
+                                .await;
+                            let _ = mgr
+                                .catalog_controller
+                                .finish_streaming_job(stream_job_id)
+                                .await.inspect_err(|err| {
+                                    tracing::error!(id = stream_job_id, error = ?err.as_report(), "failed to finish resumed background streaming job");
+                                });
+                        }
+                        // Clean up on failure too, for the same reason as in
+                        // `create_streaming_job_inner_v2`: a leftover checkpoint would just be
+                        // re-dispatched with stale `table_fragments` on the next restart.
+                        let _ = mgr
+                            .catalog_controller
+                            .remove_background_ddl_progress(stream_job_id)
+                            .await;
+                    };
+                    tokio::spawn(fut);
+                }
+                BackgroundDdlPhase::Finishing => {
+                    // Actors are already live -- the crash landed between `set_background_ddl_phase(Finishing)`
+                    // and `remove_background_ddl_progress`. Re-running `create_streaming_job` would
+                    // try to recreate actors that already exist; just finish the bookkeeping.
+                    let mgr = mgr.clone();
+                    let fut = async move {
+                        let _ = mgr
+                            .catalog_controller
+                            .finish_streaming_job(stream_job_id)
+                            .await.inspect_err(|err| {
+                                tracing::error!(id = stream_job_id, error = ?err.as_report(), "failed to finish resumed background streaming job");
+                            });
+                        let _ = mgr
+                            .catalog_controller
+                            .remove_background_ddl_progress(stream_job_id)
+                            .await;
+                    };
+                    tokio::spawn(fut);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the current [`BackgroundDdlPhase`] of a background streaming job, or `None` if it
+    /// has already finished (or was never a background job). Used by `SHOW JOBS`-style queries
+    /// to report coarse progress instead of the caller having to guess.
+    pub async fn background_ddl_phase(&self, job_id: u32) -> MetaResult<Option<BackgroundDdlPhase>> {
+        let MetadataManager::V2(mgr) = &self.metadata_manager else {
+            unreachable!("MetadataManager should be V2")
+        };
+        Ok(mgr
+            .catalog_controller
+            .get_background_ddl_progress(job_id)
+            .await?
+            .map(|progress| progress.phase))
+    }
+
     /// This is used for `ALTER TABLE ADD/DROP COLUMN`.
     pub async fn replace_table_v2(
         &self,